@@ -1,10 +1,14 @@
 use std::io::Write;
 
 use dotenv::dotenv;
+use image::RgbImage;
 use log::{info, warn};
+use rand::rngs::OsRng;
 use rand::Rng;
-use rand::SeedableRng;
-use rand_chacha::ChaCha8Rng;
+use rand_chacha::{ChaCha12Rng, ChaCha20Rng, ChaCha8Rng};
+use rand_core::{Error, RngCore, SeedableRng};
+use rand_distr::{Bernoulli, Distribution, Normal};
+use rand_pcg::Pcg64;
 
 const WIDTH: usize = 20;
 const HEIGHT: usize = 20;
@@ -12,13 +16,412 @@ const PPM_SCALER: usize = 25;
 const PPM_RANGE: f64 = 10f64;
 const PPM_COLOR_INTENSITY: f64 = 255f64;
 const SAMPLE_SIZE: usize = 75;
-const BIAS: f64 = 20f64;
 const DATA_FOLDER: &str = "data";
 const TRAIN_PASSES: usize = 2000;
 const TRAIN_SEED: u64 = 69;
 const CHECK_SEED: u64 = 420;
+// Reseed the chosen core from `OsRng` every 1 MiB of output so long runs
+// don't exhaust a single seeded stream. Override with `PERCEPTRON_RESEED_BYTES`.
+const RESEED_THRESHOLD_BYTES: u64 = 1 << 20;
+// Std-dev of the additive Gaussian noise applied to training samples; 0 disables it.
+const NOISE_SIGMA: f64 = 0f64;
+// Per-pixel flip probability for salt-and-pepper noise; 0 disables it.
+const SALT_PEPPER_P: f64 = 0f64;
+const NUM_CLASSES: usize = 4;
+// Uniform class frequencies by default; override with `PERCEPTRON_CLASS_WEIGHTS`
+// as a comma-separated list in `ShapeClass::ALL` order.
+const DEFAULT_CLASS_WEIGHTS: [f64; NUM_CLASSES] = [1f64, 1f64, 1f64, 1f64];
+// Learning rate applied to each perceptron update; 1 reproduces the original
+// hard add/subtract rule. Override with `PERCEPTRON_ETA`.
+const ETA: f64 = 1f64;
+// Starting value for each class's bias weight before training adapts it.
+// Override with `PERCEPTRON_INITIAL_BIAS`.
+const INITIAL_BIAS: f64 = 0f64;
+// k for the k×k subgrid used to estimate per-cell coverage when rasterizing
+// in coverage mode. Override with `PERCEPTRON_SUBSAMPLES`.
+const SUBSAMPLES: usize = 4;
 
 type Layer = [[f64; HEIGHT]; WIDTH];
+type ClassWeights = [Layer; NUM_CLASSES];
+type ClassBias = [f64; NUM_CLASSES];
+
+/// The shapes the discriminator is trained to tell apart. One weight `Layer`
+/// is learned per class; classification is the argmax of `feed_forward`
+/// plus the class's bias across all of them.
+#[derive(Clone, Copy, Debug)]
+enum ShapeClass {
+    Rectangle,
+    Circle,
+    Triangle,
+    Empty,
+}
+
+impl ShapeClass {
+    const ALL: [ShapeClass; NUM_CLASSES] = [
+        ShapeClass::Rectangle,
+        ShapeClass::Circle,
+        ShapeClass::Triangle,
+        ShapeClass::Empty,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ShapeClass::Rectangle => "rectangle",
+            ShapeClass::Circle => "circle",
+            ShapeClass::Triangle => "triangle",
+            ShapeClass::Empty => "empty",
+        }
+    }
+
+    fn generate<R: RngCore>(self, layer: &mut Layer, rng: &mut R, raster: RasterConfig) {
+        match self {
+            ShapeClass::Rectangle => layer_random_rect(layer, rng, raster),
+            ShapeClass::Circle => layer_random_circle(layer, rng, raster),
+            ShapeClass::Triangle => layer_random_triangle(layer, rng, raster),
+            ShapeClass::Empty => layer_random_empty(layer, rng, raster),
+        }
+    }
+}
+
+fn class_weights_from_env() -> [f64; NUM_CLASSES] {
+    let Ok(raw) = std::env::var("PERCEPTRON_CLASS_WEIGHTS") else {
+        return DEFAULT_CLASS_WEIGHTS;
+    };
+    let parsed: Vec<f64> = raw
+        .split(',')
+        .filter_map(|s| s.trim().parse().ok())
+        .collect();
+    match parsed.try_into() {
+        Ok(weights) => weights,
+        Err(_) => DEFAULT_CLASS_WEIGHTS,
+    }
+}
+
+fn eta_from_env() -> f64 {
+    env_f64("PERCEPTRON_ETA", ETA)
+}
+
+fn initial_bias_from_env() -> f64 {
+    env_f64("PERCEPTRON_INITIAL_BIAS", INITIAL_BIAS)
+}
+
+/// A Vose's alias table for O(1) weighted sampling over a fixed set of
+/// classes. Construction is O(n); each `sample` call is two RNG draws and
+/// two array lookups, regardless of how skewed the weights are.
+struct AliasTable {
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let sum: f64 = weights.iter().sum();
+        let mut scaled: Vec<f64> = weights.iter().map(|w| w * n as f64 / sum).collect();
+
+        let mut small: Vec<usize> = Vec::new();
+        let mut large: Vec<usize> = Vec::new();
+        for (i, &s) in scaled.iter().enumerate() {
+            if s < 1f64 {
+                small.push(i);
+            } else {
+                large.push(i);
+            }
+        }
+
+        let mut prob = vec![0f64; n];
+        let mut alias = vec![0usize; n];
+
+        while let Some(s) = small.pop() {
+            let l = match large.pop() {
+                Some(l) => l,
+                None => {
+                    // Floating-point rounding can starve `large` one step
+                    // ahead of `small`; treat the leftover as exact.
+                    prob[s] = 1f64;
+                    continue;
+                }
+            };
+            prob[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] -= 1f64 - scaled[s];
+            if scaled[l] < 1f64 {
+                small.push(l);
+            } else {
+                large.push(l);
+            }
+        }
+
+        for i in large.into_iter().chain(small) {
+            prob[i] = 1f64;
+        }
+
+        Self { prob, alias }
+    }
+
+    fn sample<R: RngCore>(&self, rng: &mut R) -> usize {
+        let i = rng.gen_range(0..self.prob.len());
+        let u: f64 = rng.gen();
+        if u < self.prob[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+fn argmax(scores: &[f64; NUM_CLASSES]) -> usize {
+    let mut best = 0;
+    for i in 1..NUM_CLASSES {
+        if scores[i] > scores[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Selects which `rand_core` RNG implementation backs a training run.
+/// Chosen at startup via the `PERCEPTRON_RNG` env var (defaults to `chacha8`).
+#[derive(Clone, Copy)]
+enum RngBackend {
+    ChaCha8,
+    ChaCha12,
+    ChaCha20,
+    Pcg64,
+}
+
+impl RngBackend {
+    fn from_env() -> Self {
+        match std::env::var("PERCEPTRON_RNG")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "chacha12" => RngBackend::ChaCha12,
+            "chacha20" => RngBackend::ChaCha20,
+            "pcg64" => RngBackend::Pcg64,
+            _ => RngBackend::ChaCha8,
+        }
+    }
+}
+
+/// A `RngCore` that can hold any of the supported backends, so the rest of
+/// the pipeline stays generic over a single concrete type at a time.
+enum AnyRng {
+    ChaCha8(ChaCha8Rng),
+    ChaCha12(ChaCha12Rng),
+    ChaCha20(ChaCha20Rng),
+    Pcg64(Pcg64),
+}
+
+impl AnyRng {
+    fn seed_from_backend(backend: RngBackend, seed: u64) -> Self {
+        match backend {
+            RngBackend::ChaCha8 => AnyRng::ChaCha8(ChaCha8Rng::seed_from_u64(seed)),
+            RngBackend::ChaCha12 => AnyRng::ChaCha12(ChaCha12Rng::seed_from_u64(seed)),
+            RngBackend::ChaCha20 => AnyRng::ChaCha20(ChaCha20Rng::seed_from_u64(seed)),
+            RngBackend::Pcg64 => AnyRng::Pcg64(Pcg64::seed_from_u64(seed)),
+        }
+    }
+
+    /// Replaces the inner state with a fresh instance of the same backend,
+    /// seeded from the OS entropy source.
+    fn reseed_from_os(&mut self) {
+        *self = match self {
+            AnyRng::ChaCha8(_) => {
+                AnyRng::ChaCha8(ChaCha8Rng::from_rng(OsRng).expect("OsRng is infallible"))
+            }
+            AnyRng::ChaCha12(_) => {
+                AnyRng::ChaCha12(ChaCha12Rng::from_rng(OsRng).expect("OsRng is infallible"))
+            }
+            AnyRng::ChaCha20(_) => {
+                AnyRng::ChaCha20(ChaCha20Rng::from_rng(OsRng).expect("OsRng is infallible"))
+            }
+            AnyRng::Pcg64(_) => AnyRng::Pcg64(Pcg64::from_rng(OsRng).expect("OsRng is infallible")),
+        };
+    }
+}
+
+impl RngCore for AnyRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            AnyRng::ChaCha8(r) => r.next_u32(),
+            AnyRng::ChaCha12(r) => r.next_u32(),
+            AnyRng::ChaCha20(r) => r.next_u32(),
+            AnyRng::Pcg64(r) => r.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            AnyRng::ChaCha8(r) => r.next_u64(),
+            AnyRng::ChaCha12(r) => r.next_u64(),
+            AnyRng::ChaCha20(r) => r.next_u64(),
+            AnyRng::Pcg64(r) => r.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            AnyRng::ChaCha8(r) => r.fill_bytes(dest),
+            AnyRng::ChaCha12(r) => r.fill_bytes(dest),
+            AnyRng::ChaCha20(r) => r.fill_bytes(dest),
+            AnyRng::Pcg64(r) => r.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Wraps an [`AnyRng`] and transparently reseeds it from `OsRng` once at
+/// least `threshold` bytes have been drawn from the current stream. This lets
+/// long training runs trade a fixed seed's reproducibility for statistical
+/// independence, without the caller having to manage reseeding by hand.
+struct ReseedingRng {
+    rng: AnyRng,
+    threshold: u64,
+    generated: u64,
+}
+
+impl ReseedingRng {
+    fn new(rng: AnyRng, threshold: u64) -> Self {
+        Self {
+            rng,
+            threshold,
+            generated: 0,
+        }
+    }
+
+    fn reseed_if_due(&mut self) {
+        if self.generated >= self.threshold {
+            self.rng.reseed_from_os();
+            self.generated = 0;
+        }
+    }
+}
+
+impl RngCore for ReseedingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.reseed_if_due();
+        self.generated += 4;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.reseed_if_due();
+        self.generated += 8;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.reseed_if_due();
+        self.generated += dest.len() as u64;
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+fn reseed_threshold_from_env() -> u64 {
+    std::env::var("PERCEPTRON_RESEED_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(RESEED_THRESHOLD_BYTES)
+}
+
+/// Data-augmentation applied to training samples only; `check_pass` always
+/// sees clean inputs so the reported fail rate measures clean-data accuracy.
+#[derive(Clone, Copy)]
+enum NoiseMode {
+    None,
+    Gaussian { sigma: f64 },
+    SaltAndPepper { p: f64 },
+}
+
+impl NoiseMode {
+    fn from_env() -> Self {
+        match std::env::var("PERCEPTRON_NOISE")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "gaussian" => NoiseMode::Gaussian {
+                sigma: env_f64("PERCEPTRON_NOISE_SIGMA", NOISE_SIGMA),
+            },
+            "salt_pepper" => NoiseMode::SaltAndPepper {
+                p: env_f64("PERCEPTRON_NOISE_P", SALT_PEPPER_P),
+            },
+            _ => NoiseMode::None,
+        }
+    }
+}
+
+fn env_f64(key: &str, default: f64) -> f64 {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Perturbs `layer` in place according to `noise`. A no-op for `NoiseMode::None`
+/// or a non-positive sigma/probability.
+fn apply_noise<R: RngCore>(layer: &mut Layer, noise: NoiseMode, rng: &mut R) {
+    match noise {
+        NoiseMode::None => {}
+        NoiseMode::Gaussian { sigma } => {
+            if sigma <= 0f64 {
+                return;
+            }
+            let normal = Normal::new(0f64, sigma).expect("sigma must be finite and positive");
+            for cell in layer.iter_mut().flatten() {
+                *cell += normal.sample(rng);
+            }
+        }
+        NoiseMode::SaltAndPepper { p } => {
+            if p <= 0f64 {
+                return;
+            }
+            let bernoulli = Bernoulli::new(p).expect("p must be in [0, 1]");
+            for cell in layer.iter_mut().flatten() {
+                if bernoulli.sample(rng) {
+                    *cell = if rng.gen_bool(0.5) { 1f64 } else { 0f64 };
+                }
+            }
+        }
+    }
+}
+
+/// Controls whether `layer_random_*` rasterizes shapes as hard 0/1 values or
+/// as the fractional area of each cell covered by the shape, and at what
+/// subsample granularity coverage is estimated.
+#[derive(Clone, Copy)]
+struct RasterConfig {
+    coverage: bool,
+    subsamples: usize,
+}
+
+impl RasterConfig {
+    fn from_env() -> Self {
+        let coverage = std::env::var("PERCEPTRON_COVERAGE")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        let subsamples = std::env::var("PERCEPTRON_SUBSAMPLES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&n: &usize| n > 0)
+            .unwrap_or(SUBSAMPLES);
+        Self {
+            coverage,
+            subsamples,
+        }
+    }
+}
 
 #[inline]
 fn clampi(x: i32, low: i32, hight: i32) -> i32 {
@@ -71,6 +474,185 @@ fn layer_fill_circle(layer: &mut Layer, cx: i32, cy: i32, r: i32, value: f64) {
     }
 }
 
+/// Exact area of the intersection between grid cell `(cell_x, cell_y)` and
+/// the rectangle `[rx0, rx1) x [ry0, ry1)`, in cell units.
+fn rect_cell_coverage(cell_x: i32, cell_y: i32, rx0: f64, ry0: f64, rx1: f64, ry1: f64) -> f64 {
+    let cx0 = cell_x as f64;
+    let cy0 = cell_y as f64;
+
+    let ox0 = rx0.max(cx0);
+    let oy0 = ry0.max(cy0);
+    let ox1 = rx1.min(cx0 + 1f64);
+    let oy1 = ry1.min(cy0 + 1f64);
+
+    (ox1 - ox0).max(0f64) * (oy1 - oy0).max(0f64)
+}
+
+fn layer_fill_rect_coverage(layer: &mut Layer, rx0: f64, ry0: f64, rx1: f64, ry1: f64, value: f64) {
+    let x0 = clampi(rx0.floor() as i32, 0, (WIDTH - 1) as i32);
+    let y0 = clampi(ry0.floor() as i32, 0, (HEIGHT - 1) as i32);
+    let x1 = clampi((rx1.ceil() as i32 - 1).max(x0), 0, (WIDTH - 1) as i32);
+    let y1 = clampi((ry1.ceil() as i32 - 1).max(y0), 0, (HEIGHT - 1) as i32);
+
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let coverage = rect_cell_coverage(x, y, rx0, ry0, rx1, ry1);
+            layer[y as usize][x as usize] = coverage * value;
+        }
+    }
+}
+
+/// Estimates the fraction of grid cell `(cell_x, cell_y)` inside the circle
+/// centered at `(cx, cy)` with radius `r` by supersampling it on a
+/// `subsamples x subsamples` subgrid.
+fn circle_cell_coverage(
+    cell_x: i32,
+    cell_y: i32,
+    cx: f64,
+    cy: f64,
+    r: f64,
+    subsamples: usize,
+) -> f64 {
+    let n = subsamples.max(1);
+    let mut hits = 0usize;
+
+    for sy in 0..n {
+        for sx in 0..n {
+            let px = cell_x as f64 + (sx as f64 + 0.5) / n as f64;
+            let py = cell_y as f64 + (sy as f64 + 0.5) / n as f64;
+            let dx = px - cx;
+            let dy = py - cy;
+            if dx * dx + dy * dy <= r * r {
+                hits += 1;
+            }
+        }
+    }
+
+    hits as f64 / (n * n) as f64
+}
+
+fn layer_fill_circle_coverage(
+    layer: &mut Layer,
+    cx: f64,
+    cy: f64,
+    r: f64,
+    value: f64,
+    subsamples: usize,
+) {
+    let x0 = clampi((cx - r).floor() as i32, 0, (WIDTH - 1) as i32);
+    let y0 = clampi((cy - r).floor() as i32, 0, (HEIGHT - 1) as i32);
+    let x1 = clampi((cx + r).ceil() as i32, 0, (WIDTH - 1) as i32);
+    let y1 = clampi((cy + r).ceil() as i32, 0, (HEIGHT - 1) as i32);
+
+    for y in y0..=y1 {
+        for x in x0..=x1 {
+            let coverage = circle_cell_coverage(x, y, cx, cy, r, subsamples);
+            if coverage > 0f64 {
+                layer[y as usize][x as usize] = coverage * value;
+            }
+        }
+    }
+}
+
+fn triangle_edge_sign_f64(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    (p.0 - b.0) * (a.1 - b.1) - (a.0 - b.0) * (p.1 - b.1)
+}
+
+fn point_in_triangle_f64(p: (f64, f64), p0: (f64, f64), p1: (f64, f64), p2: (f64, f64)) -> bool {
+    let d1 = triangle_edge_sign_f64(p, p0, p1);
+    let d2 = triangle_edge_sign_f64(p, p1, p2);
+    let d3 = triangle_edge_sign_f64(p, p2, p0);
+
+    let has_neg = d1 < 0f64 || d2 < 0f64 || d3 < 0f64;
+    let has_pos = d1 > 0f64 || d2 > 0f64 || d3 > 0f64;
+
+    !(has_neg && has_pos)
+}
+
+/// Same supersampling strategy as `circle_cell_coverage`, applied to a triangle.
+fn triangle_cell_coverage(
+    cell_x: i32,
+    cell_y: i32,
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    subsamples: usize,
+) -> f64 {
+    let n = subsamples.max(1);
+    let mut hits = 0usize;
+
+    for sy in 0..n {
+        for sx in 0..n {
+            let px = cell_x as f64 + (sx as f64 + 0.5) / n as f64;
+            let py = cell_y as f64 + (sy as f64 + 0.5) / n as f64;
+            if point_in_triangle_f64((px, py), p0, p1, p2) {
+                hits += 1;
+            }
+        }
+    }
+
+    hits as f64 / (n * n) as f64
+}
+
+fn layer_fill_triangle_coverage(
+    layer: &mut Layer,
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    value: f64,
+    subsamples: usize,
+) {
+    let min_x = clampi(p0.0.min(p1.0).min(p2.0).floor() as i32, 0, (WIDTH - 1) as i32);
+    let max_x = clampi(p0.0.max(p1.0).max(p2.0).ceil() as i32, 0, (WIDTH - 1) as i32);
+    let min_y = clampi(p0.1.min(p1.1).min(p2.1).floor() as i32, 0, (HEIGHT - 1) as i32);
+    let max_y = clampi(p0.1.max(p1.1).max(p2.1).ceil() as i32, 0, (HEIGHT - 1) as i32);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let coverage = triangle_cell_coverage(x, y, p0, p1, p2, subsamples);
+            if coverage > 0f64 {
+                layer[y as usize][x as usize] = coverage * value;
+            }
+        }
+    }
+}
+
+fn triangle_edge_sign(p: (i32, i32), a: (i32, i32), b: (i32, i32)) -> i32 {
+    (p.0 - b.0) * (a.1 - b.1) - (a.0 - b.0) * (p.1 - b.1)
+}
+
+fn layer_fill_triangle(
+    layer: &mut Layer,
+    p0: (i32, i32),
+    p1: (i32, i32),
+    p2: (i32, i32),
+    value: f64,
+) {
+    let (x0, y0) = p0;
+    let (x1, y1) = p1;
+    let (x2, y2) = p2;
+    let min_x = clampi(x0.min(x1).min(x2), 0, (WIDTH - 1) as i32);
+    let max_x = clampi(x0.max(x1).max(x2), 0, (WIDTH - 1) as i32);
+    let min_y = clampi(y0.min(y1).min(y2), 0, (HEIGHT - 1) as i32);
+    let max_y = clampi(y0.max(y1).max(y2), 0, (HEIGHT - 1) as i32);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let p = (x, y);
+            let d1 = triangle_edge_sign(p, p0, p1);
+            let d2 = triangle_edge_sign(p, p1, p2);
+            let d3 = triangle_edge_sign(p, p2, p0);
+
+            let has_neg = d1 < 0 || d2 < 0 || d3 < 0;
+            let has_pos = d1 > 0 || d2 > 0 || d3 > 0;
+
+            if !(has_neg && has_pos) {
+                layer[y as usize][x as usize] = value;
+            }
+        }
+    }
+}
+
 fn layer_save_as_ppm(layer: &Layer, file_path: &str) -> std::io::Result<()> {
     let mut f = std::fs::File::create(file_path)?;
 
@@ -97,6 +679,204 @@ fn layer_save_as_ppm(layer: &Layer, file_path: &str) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Output container for weight-evolution frames. PPM is the original format;
+/// PNG trades the trivial writer for a file viewers open natively.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Ppm,
+    Png,
+}
+
+impl OutputFormat {
+    fn from_env() -> Self {
+        match std::env::var("PERCEPTRON_OUTPUT_FORMAT")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "png" => OutputFormat::Png,
+            _ => OutputFormat::Ppm,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Ppm => "ppm",
+            OutputFormat::Png => "png",
+        }
+    }
+}
+
+/// Maps a normalized `[0, 1]` weight value to an RGB color. `Legacy`
+/// reproduces the original blue-vs-white two-color ramp byte-for-byte;
+/// the others are gradients meant for signed weights or perceptual heatmaps.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColorMap {
+    Legacy,
+    Diverging,
+    Heatmap,
+}
+
+const DIVERGING_STOPS: [(f64, (u8, u8, u8)); 3] =
+    [(0f64, (0, 0, 255)), (0.5, (255, 255, 255)), (1f64, (255, 0, 0))];
+
+const HEATMAP_STOPS: [(f64, (u8, u8, u8)); 4] = [
+    (0f64, (0, 0, 0)),
+    (0.33, (128, 0, 128)),
+    (0.66, (255, 128, 0)),
+    (1f64, (255, 255, 0)),
+];
+
+impl ColorMap {
+    fn from_env() -> Self {
+        match std::env::var("PERCEPTRON_COLORMAP")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "diverging" => ColorMap::Diverging,
+            "heatmap" => ColorMap::Heatmap,
+            _ => ColorMap::Legacy,
+        }
+    }
+
+    fn pixel(self, s: f64, blend: Blend) -> (u8, u8, u8) {
+        match self {
+            ColorMap::Legacy => {
+                let r = (PPM_COLOR_INTENSITY * (1f64 - s)).floor() as u8;
+                let b = (PPM_COLOR_INTENSITY * s).floor() as u8;
+                (r, r, b)
+            }
+            ColorMap::Diverging => sample_gradient(&DIVERGING_STOPS, s, blend),
+            ColorMap::Heatmap => sample_gradient(&HEATMAP_STOPS, s, blend),
+        }
+    }
+}
+
+/// How to interpolate between the two gradient stops bracketing a sample.
+#[derive(Clone, Copy)]
+enum Blend {
+    Linear,
+    Smoothstep,
+}
+
+impl Blend {
+    fn from_env() -> Self {
+        match std::env::var("PERCEPTRON_BLEND")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "smoothstep" => Blend::Smoothstep,
+            _ => Blend::Linear,
+        }
+    }
+
+    fn apply(self, t: f64) -> f64 {
+        match self {
+            Blend::Linear => t,
+            Blend::Smoothstep => t * t * (3f64 - 2f64 * t),
+        }
+    }
+}
+
+fn lerp_color(a: (u8, u8, u8), b: (u8, u8, u8), t: f64) -> (u8, u8, u8) {
+    let lerp = |x: u8, y: u8| (x as f64 + (y as f64 - x as f64) * t).round() as u8;
+    (lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2))
+}
+
+fn sample_gradient(stops: &[(f64, (u8, u8, u8))], s: f64, blend: Blend) -> (u8, u8, u8) {
+    let s = s.clamp(0f64, 1f64);
+    for pair in stops.windows(2) {
+        let (t0, c0) = pair[0];
+        let (t1, c1) = pair[1];
+        if s <= t1 {
+            let span = (t1 - t0).max(f64::EPSILON);
+            let t = blend.apply(((s - t0) / span).clamp(0f64, 1f64));
+            return lerp_color(c0, c1, t);
+        }
+    }
+    stops.last().unwrap().1
+}
+
+fn layer_pixels(layer: &Layer, colormap: ColorMap, blend: Blend) -> Vec<u8> {
+    let mut pixels = Vec::with_capacity(WIDTH * PPM_SCALER * HEIGHT * PPM_SCALER * 3);
+    for y in 0..HEIGHT * PPM_SCALER {
+        for x in 0..WIDTH * PPM_SCALER {
+            let s = (layer[y / PPM_SCALER][x / PPM_SCALER] + PPM_RANGE) / (2f64 * PPM_RANGE);
+            let (r, g, b) = colormap.pixel(s, blend);
+            pixels.extend_from_slice(&[r, g, b]);
+        }
+    }
+    pixels
+}
+
+fn layer_save_as_ppm_with_colormap(
+    layer: &Layer,
+    file_path: &str,
+    colormap: ColorMap,
+    blend: Blend,
+) -> std::io::Result<()> {
+    let mut f = std::fs::File::create(file_path)?;
+    write!(
+        &mut f,
+        "P6\n{} {} 255\n",
+        WIDTH * PPM_SCALER,
+        HEIGHT * PPM_SCALER
+    )?;
+    f.write_all(&layer_pixels(layer, colormap, blend))?;
+    Ok(())
+}
+
+fn layer_save_as_png(
+    layer: &Layer,
+    file_path: &str,
+    colormap: ColorMap,
+    blend: Blend,
+) -> std::io::Result<()> {
+    let width = (WIDTH * PPM_SCALER) as u32;
+    let height = (HEIGHT * PPM_SCALER) as u32;
+    let pixels = layer_pixels(layer, colormap, blend);
+    let image = RgbImage::from_raw(width, height, pixels)
+        .expect("pixel buffer matches the declared image dimensions");
+    image
+        .save(file_path)
+        .map_err(std::io::Error::other)
+}
+
+/// Bundles the output format and color mapping so `train_pass` can pick
+/// publication-quality weight-evolution frames without growing more params.
+#[derive(Clone, Copy)]
+struct OutputConfig {
+    format: OutputFormat,
+    colormap: ColorMap,
+    blend: Blend,
+}
+
+impl OutputConfig {
+    fn from_env() -> Self {
+        Self {
+            format: OutputFormat::from_env(),
+            colormap: ColorMap::from_env(),
+            blend: Blend::from_env(),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        self.format.extension()
+    }
+
+    fn save(self, layer: &Layer, file_path: &str) -> std::io::Result<()> {
+        match (self.format, self.colormap) {
+            (OutputFormat::Ppm, ColorMap::Legacy) => layer_save_as_ppm(layer, file_path),
+            (OutputFormat::Ppm, _) => {
+                layer_save_as_ppm_with_colormap(layer, file_path, self.colormap, self.blend)
+            }
+            (OutputFormat::Png, _) => layer_save_as_png(layer, file_path, self.colormap, self.blend),
+        }
+    }
+}
+
 fn feed_forward(inputs: &Layer, weights: &Layer) -> f64 {
     let mut ouput: f64 = 0f64;
 
@@ -109,24 +889,34 @@ fn feed_forward(inputs: &Layer, weights: &Layer) -> f64 {
     ouput
 }
 
-fn add_inputs_from_weights(inputs: &Layer, weights: &mut Layer) {
+fn add_inputs_from_weights(inputs: &Layer, weights: &mut Layer, eta: f64) {
     for y in 0..HEIGHT {
         for x in 0..WIDTH {
-            weights[y][x] += inputs[y][x]
+            weights[y][x] += eta * inputs[y][x]
         }
     }
 }
 
-fn sub_inputs_from_weights(inputs: &Layer, weights: &mut Layer) {
+fn sub_inputs_from_weights(inputs: &Layer, weights: &mut Layer, eta: f64) {
     for y in 0..HEIGHT {
         for x in 0..WIDTH {
-            weights[y][x] -= inputs[y][x]
+            weights[y][x] -= eta * inputs[y][x]
         }
     }
 }
 
-fn layer_random_rect(layer: &mut Layer, rng: &mut ChaCha8Rng) {
+fn layer_random_rect<R: RngCore>(layer: &mut Layer, rng: &mut R, raster: RasterConfig) {
     layer_fill_rect(layer, 0, 0, WIDTH as i32, HEIGHT as i32, 0f64);
+
+    if raster.coverage {
+        let x: f64 = rng.gen_range(0f64..WIDTH as f64);
+        let y: f64 = rng.gen_range(0f64..HEIGHT as f64);
+        let w: f64 = rng.gen_range(1f64..(WIDTH as f64 - x).max(2f64));
+        let h: f64 = rng.gen_range(1f64..(HEIGHT as f64 - y).max(2f64));
+        layer_fill_rect_coverage(layer, x, y, x + w, y + h, 1f64);
+        return;
+    }
+
     let x = rng.gen_range(0..WIDTH);
     let y = rng.gen_range(0..HEIGHT);
 
@@ -136,7 +926,7 @@ fn layer_random_rect(layer: &mut Layer, rng: &mut ChaCha8Rng) {
     }
     w = rng.gen_range(1..w);
 
-    let mut h = HEIGHT - x;
+    let mut h = HEIGHT - y;
     if h < 2 {
         h = 2
     }
@@ -145,8 +935,21 @@ fn layer_random_rect(layer: &mut Layer, rng: &mut ChaCha8Rng) {
     layer_fill_rect(layer, x as i32, y as i32, w as i32, h as i32, 1f64);
 }
 
-fn layer_random_circle(layer: &mut Layer, rng: &mut ChaCha8Rng) {
+fn layer_random_circle<R: RngCore>(layer: &mut Layer, rng: &mut R, raster: RasterConfig) {
     layer_fill_rect(layer, 0, 0, WIDTH as i32, HEIGHT as i32, 0f64);
+
+    if raster.coverage {
+        let cx: f64 = rng.gen_range(0f64..WIDTH as f64);
+        let cy: f64 = rng.gen_range(0f64..HEIGHT as f64);
+        let max_r = [cx, cy, WIDTH as f64 - cx, HEIGHT as f64 - cy]
+            .into_iter()
+            .fold(f64::MAX, f64::min)
+            .max(2f64);
+        let r: f64 = rng.gen_range(1f64..max_r);
+        layer_fill_circle_coverage(layer, cx, cy, r, 1f64, raster.subsamples);
+        return;
+    }
+
     let cx: i32 = rng.gen_range(0..WIDTH).try_into().unwrap();
     let cy = rng.gen_range(0..HEIGHT).try_into().unwrap();
     let mut r = i32::MAX;
@@ -169,34 +972,86 @@ fn layer_random_circle(layer: &mut Layer, rng: &mut ChaCha8Rng) {
     layer_fill_circle(layer, cx as i32, cy as i32, r, 1f64);
 }
 
-fn train_pass(
+fn layer_random_triangle<R: RngCore>(layer: &mut Layer, rng: &mut R, raster: RasterConfig) {
+    layer_fill_rect(layer, 0, 0, WIDTH as i32, HEIGHT as i32, 0f64);
+
+    if raster.coverage {
+        let random_point = |rng: &mut R| {
+            (
+                rng.gen_range(0f64..WIDTH as f64),
+                rng.gen_range(0f64..HEIGHT as f64),
+            )
+        };
+        let p0 = random_point(rng);
+        let p1 = random_point(rng);
+        let p2 = random_point(rng);
+
+        layer_fill_triangle_coverage(layer, p0, p1, p2, 1f64, raster.subsamples);
+        return;
+    }
+
+    let random_point =
+        |rng: &mut R| (rng.gen_range(0..WIDTH) as i32, rng.gen_range(0..HEIGHT) as i32);
+    let p0 = random_point(rng);
+    let p1 = random_point(rng);
+    let p2 = random_point(rng);
+
+    layer_fill_triangle(layer, p0, p1, p2, 1f64);
+}
+
+fn layer_random_empty<R: RngCore>(layer: &mut Layer, _rng: &mut R, _raster: RasterConfig) {
+    layer_fill_rect(layer, 0, 0, WIDTH as i32, HEIGHT as i32, 0f64);
+}
+
+/// Bundles the knobs that shape a training run so `train_pass` doesn't need
+/// to grow a new positional parameter every time one is added.
+struct TrainConfig<'a> {
+    noise: NoiseMode,
+    class_table: &'a AliasTable,
+    eta: f64,
+    raster: RasterConfig,
+    output: OutputConfig,
+}
+
+fn train_pass<R: RngCore>(
     inputs: &mut Layer,
-    weights: &mut Layer,
-    rng: &mut ChaCha8Rng,
+    weights: &mut ClassWeights,
+    bias: &mut ClassBias,
+    rng: &mut R,
+    config: &TrainConfig,
 ) -> std::io::Result<i32> {
     let mut count: usize = 0;
     let mut adjusted: i32 = 0;
 
     for _ in 0..SAMPLE_SIZE {
-        layer_random_rect(inputs, rng);
-        if feed_forward(inputs, weights) > BIAS {
-            sub_inputs_from_weights(inputs, weights);
-            let file_path =
-                format!("{}/weights-{:0>3}.ppm", DATA_FOLDER, count);
-            count += 1;
-            info!("saving: {}", &file_path);
-            layer_save_as_ppm(weights, &file_path)?;
-            adjusted += 1;
-        }
+        let class_idx = config.class_table.sample(rng);
+        let class = ShapeClass::ALL[class_idx];
+        class.generate(inputs, rng, config.raster);
+        apply_noise(inputs, config.noise, rng);
+
+        let scores: [f64; NUM_CLASSES] =
+            core::array::from_fn(|c| feed_forward(inputs, &weights[c]) + bias[c]);
+        let predicted = argmax(&scores);
 
-        layer_random_circle(inputs, rng);
-        if feed_forward(inputs, weights) < BIAS {
-            add_inputs_from_weights(inputs, weights);
-            let file_path =
-                format!("{}/weights-{:0>3}.ppm", DATA_FOLDER, count);
+        if predicted != class_idx {
+            add_inputs_from_weights(inputs, &mut weights[class_idx], config.eta);
+            bias[class_idx] += config.eta;
+            sub_inputs_from_weights(inputs, &mut weights[predicted], config.eta);
+            bias[predicted] -= config.eta;
+            let file_path = format!(
+                "{}/weights-{:0>3}.{}",
+                DATA_FOLDER,
+                count,
+                config.output.extension()
+            );
             count += 1;
-            info!("saving: {}", &file_path);
-            layer_save_as_ppm(weights, &file_path)?;
+            info!(
+                "saving: {} ({} misclassified as {})",
+                &file_path,
+                class.name(),
+                ShapeClass::ALL[predicted].name()
+            );
+            config.output.save(&weights[class_idx], &file_path)?;
             adjusted += 1;
         }
     }
@@ -204,22 +1059,25 @@ fn train_pass(
     Ok(adjusted)
 }
 
-fn check_pass(
+fn check_pass<R: RngCore>(
     inputs: &mut Layer,
-    weights: &mut Layer,
-    rng: &mut ChaCha8Rng,
+    weights: &ClassWeights,
+    bias: &ClassBias,
+    rng: &mut R,
+    class_table: &AliasTable,
+    raster: RasterConfig,
 ) -> i32 {
     let mut adjusted: i32 = 0;
 
     for _ in 0..SAMPLE_SIZE {
-        layer_random_rect(inputs, rng);
-        if feed_forward(inputs, weights) > BIAS {
-            adjusted += 1;
-        }
+        let class_idx = class_table.sample(rng);
+        let class = ShapeClass::ALL[class_idx];
+        class.generate(inputs, rng, raster);
 
-        layer_random_circle(inputs, rng);
-        if feed_forward(inputs, weights) < BIAS {
-            adjusted -= 1;
+        let scores: [f64; NUM_CLASSES] =
+            core::array::from_fn(|c| feed_forward(inputs, &weights[c]) + bias[c]);
+        if argmax(&scores) != class_idx {
+            adjusted += 1;
         }
     }
     adjusted
@@ -230,32 +1088,265 @@ fn main() -> std::io::Result<()> {
     pretty_env_logger::init();
 
     let mut inputs: Layer = [[0f64; HEIGHT]; WIDTH];
-    let mut weights: Layer = [[0f64; HEIGHT]; WIDTH];
+    let mut weights: ClassWeights = [[[0f64; HEIGHT]; WIDTH]; NUM_CLASSES];
+    let initial_bias = initial_bias_from_env();
+    let mut bias: ClassBias = [initial_bias; NUM_CLASSES];
+    let eta = eta_from_env();
 
     std::fs::create_dir_all(DATA_FOLDER)?;
 
-    let mut rng = ChaCha8Rng::seed_from_u64(CHECK_SEED);
-    let adj = check_pass(&mut inputs, &mut weights, &mut rng);
+    let backend = RngBackend::from_env();
+    let reseed_threshold = reseed_threshold_from_env();
+    let noise = NoiseMode::from_env();
+    let class_table = AliasTable::new(&class_weights_from_env());
+    let raster = RasterConfig::from_env();
+    let output = OutputConfig::from_env();
+
+    let mut rng = ReseedingRng::new(
+        AnyRng::seed_from_backend(backend, CHECK_SEED),
+        reseed_threshold,
+    );
+    let adj = check_pass(&mut inputs, &weights, &bias, &mut rng, &class_table, raster);
     warn!(
         "fail rate of untrained model is {}",
-        adj as f64 / (SAMPLE_SIZE as f64 * 2f64),
+        adj as f64 / SAMPLE_SIZE as f64,
     );
 
+    let train_config = TrainConfig {
+        noise,
+        class_table: &class_table,
+        eta,
+        raster,
+        output,
+    };
+
+    let mut rng = ReseedingRng::new(
+        AnyRng::seed_from_backend(backend, TRAIN_SEED),
+        reseed_threshold,
+    );
     for i in 0..TRAIN_PASSES {
-        let mut rng = ChaCha8Rng::seed_from_u64(TRAIN_SEED);
-        let adj = train_pass(&mut inputs, &mut weights, &mut rng)?;
+        let adj = train_pass(&mut inputs, &mut weights, &mut bias, &mut rng, &train_config)?;
         info!("Pass: {}: adjusted {} times", i, adj);
         if adj <= 0 {
             break;
         }
     }
 
-    let mut rng = ChaCha8Rng::seed_from_u64(CHECK_SEED);
-    let adj = check_pass(&mut inputs, &mut weights, &mut rng);
+    let mut rng = ReseedingRng::new(
+        AnyRng::seed_from_backend(backend, CHECK_SEED),
+        reseed_threshold,
+    );
+    let adj = check_pass(&mut inputs, &weights, &bias, &mut rng, &class_table, raster);
     warn!(
         "fail rate trained model is {}",
-        adj as f64 / (SAMPLE_SIZE as f64 * 2f64),
+        adj as f64 / SAMPLE_SIZE as f64,
     );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alias_table_initializes_every_index() {
+        for weights in [&[1f64, 1f64, 1f64, 1f64][..], &[1f64, 1f64, 1f64, 7f64][..]] {
+            let table = AliasTable::new(weights);
+            for i in 0..weights.len() {
+                assert!(
+                    table.prob[i] > 0f64 || table.alias[i] != 0,
+                    "index {} was never assigned a prob/alias pair",
+                    i
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn alias_table_sample_matches_weight_ratios() {
+        let weights = [1f64, 1f64, 1f64, 7f64];
+        let table = AliasTable::new(&weights);
+        let mut rng = ChaCha8Rng::seed_from_u64(420);
+
+        let draws = 200_000;
+        let mut counts = [0u64; 4];
+        for _ in 0..draws {
+            counts[table.sample(&mut rng)] += 1;
+        }
+
+        let total: f64 = weights.iter().sum();
+        for (i, &count) in counts.iter().enumerate() {
+            let expected = weights[i] / total;
+            let observed = count as f64 / draws as f64;
+            assert!(
+                (observed - expected).abs() < 0.01,
+                "class {} observed {} expected {}",
+                i,
+                observed,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn apply_noise_none_is_noop() {
+        let original: Layer = [[0.5f64; HEIGHT]; WIDTH];
+        let mut layer = original;
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        apply_noise(&mut layer, NoiseMode::None, &mut rng);
+        assert_eq!(layer, original);
+    }
+
+    #[test]
+    fn apply_noise_zero_sigma_is_noop() {
+        let original: Layer = [[0.5f64; HEIGHT]; WIDTH];
+        let mut layer = original;
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        apply_noise(&mut layer, NoiseMode::Gaussian { sigma: 0f64 }, &mut rng);
+        assert_eq!(layer, original);
+    }
+
+    #[test]
+    fn apply_noise_zero_p_is_noop() {
+        let original: Layer = [[0.5f64; HEIGHT]; WIDTH];
+        let mut layer = original;
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        apply_noise(&mut layer, NoiseMode::SaltAndPepper { p: 0f64 }, &mut rng);
+        assert_eq!(layer, original);
+    }
+
+    #[test]
+    fn apply_noise_gaussian_perturbs_values() {
+        let original: Layer = [[0.5f64; HEIGHT]; WIDTH];
+        let mut layer = original;
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        apply_noise(&mut layer, NoiseMode::Gaussian { sigma: 1f64 }, &mut rng);
+        assert_ne!(layer, original);
+    }
+
+    #[test]
+    fn weight_update_helpers_scale_by_eta() {
+        let mut inputs: Layer = [[0f64; HEIGHT]; WIDTH];
+        inputs[0][0] = 2f64;
+        inputs[5][5] = -3f64;
+
+        let mut added_eta1: Layer = [[0f64; HEIGHT]; WIDTH];
+        add_inputs_from_weights(&inputs, &mut added_eta1, 1f64);
+        let mut added_eta2: Layer = [[0f64; HEIGHT]; WIDTH];
+        add_inputs_from_weights(&inputs, &mut added_eta2, 2f64);
+        assert_eq!(added_eta2[0][0], added_eta1[0][0] * 2f64);
+        assert_eq!(added_eta2[5][5], added_eta1[5][5] * 2f64);
+
+        let mut subbed_eta1: Layer = [[0f64; HEIGHT]; WIDTH];
+        sub_inputs_from_weights(&inputs, &mut subbed_eta1, 1f64);
+        assert_eq!(subbed_eta1[0][0], -inputs[0][0]);
+        assert_eq!(subbed_eta1[5][5], -inputs[5][5]);
+    }
+
+    #[test]
+    fn train_pass_bias_updates_are_eta_sized_and_balanced() {
+        std::fs::create_dir_all(DATA_FOLDER).unwrap();
+
+        let mut inputs: Layer = [[0f64; HEIGHT]; WIDTH];
+        let mut weights: ClassWeights = [[[0f64; HEIGHT]; WIDTH]; NUM_CLASSES];
+        let mut bias: ClassBias = [0f64; NUM_CLASSES];
+        let class_table = AliasTable::new(&DEFAULT_CLASS_WEIGHTS);
+        let raster = RasterConfig {
+            coverage: false,
+            subsamples: SUBSAMPLES,
+        };
+        let output = OutputConfig {
+            format: OutputFormat::Ppm,
+            colormap: ColorMap::Legacy,
+            blend: Blend::Linear,
+        };
+        let eta = 2.5f64;
+        let config = TrainConfig {
+            noise: NoiseMode::None,
+            class_table: &class_table,
+            eta,
+            raster,
+            output,
+        };
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+
+        let adjusted = train_pass(&mut inputs, &mut weights, &mut bias, &mut rng, &config).unwrap();
+
+        assert!(
+            adjusted > 0,
+            "expected at least one misclassification to exercise the update rule"
+        );
+        // Every misclassification adds +eta to the true class's bias and
+        // -eta to the predicted class's, so the totals always cancel out.
+        let sum: f64 = bias.iter().sum();
+        assert!(
+            sum.abs() < 1e-9,
+            "bias updates should cancel out (+eta true, -eta predicted), got sum {}",
+            sum
+        );
+        for &b in bias.iter() {
+            let ratio = b / eta;
+            assert!(
+                (ratio - ratio.round()).abs() < 1e-9,
+                "bias {} is not a multiple of eta {}",
+                b,
+                eta
+            );
+        }
+    }
+
+    #[test]
+    fn rect_cell_coverage_inside_and_outside() {
+        assert_eq!(rect_cell_coverage(5, 5, 0f64, 0f64, 20f64, 20f64), 1f64);
+        assert_eq!(rect_cell_coverage(5, 5, 10f64, 10f64, 15f64, 15f64), 0f64);
+    }
+
+    #[test]
+    fn circle_cell_coverage_inside_and_outside() {
+        // Cell (10, 10) is fully enclosed by a large circle centered on it.
+        assert_eq!(circle_cell_coverage(10, 10, 10.5, 10.5, 5f64, 4), 1f64);
+        // A far-away circle never reaches this cell.
+        assert_eq!(circle_cell_coverage(10, 10, 0f64, 0f64, 1f64, 4), 0f64);
+    }
+
+    #[test]
+    fn triangle_cell_coverage_inside_and_outside() {
+        let p0 = (0f64, 0f64);
+        let p1 = (20f64, 0f64);
+        let p2 = (0f64, 20f64);
+        // Cell (1, 1) sits well inside the large triangle's hypotenuse.
+        assert_eq!(triangle_cell_coverage(1, 1, p0, p1, p2, 4), 1f64);
+        // Cell (15, 15) is beyond the hypotenuse, outside the triangle.
+        assert_eq!(triangle_cell_coverage(15, 15, p0, p1, p2, 4), 0f64);
+    }
+
+    #[test]
+    fn sample_gradient_hits_exact_stop_colors() {
+        assert_eq!(
+            sample_gradient(&DIVERGING_STOPS, 0f64, Blend::Linear),
+            (0, 0, 255)
+        );
+        assert_eq!(
+            sample_gradient(&DIVERGING_STOPS, 0.5, Blend::Linear),
+            (255, 255, 255)
+        );
+        assert_eq!(
+            sample_gradient(&DIVERGING_STOPS, 1f64, Blend::Linear),
+            (255, 0, 0)
+        );
+    }
+
+    #[test]
+    fn lerp_color_interpolates_channels() {
+        assert_eq!(lerp_color((0, 0, 0), (255, 255, 255), 0f64), (0, 0, 0));
+        assert_eq!(
+            lerp_color((0, 0, 0), (255, 255, 255), 1f64),
+            (255, 255, 255)
+        );
+        assert_eq!(
+            lerp_color((0, 0, 0), (255, 255, 255), 0.5),
+            (128, 128, 128)
+        );
+    }
+}